@@ -1,8 +1,14 @@
+use std::ops::{Div, Mul, Sub};
+
+use crate::point::Point;
 use crate::polygon::LineSegment;
 
 // Computes the intersection of a line segment and a scanline.
 // Returns the intersection point as a tuple (x, y).
-pub fn intersection(segment: LineSegment, y: f64) -> (f64, f64) {
+pub fn intersection<T>(segment: LineSegment<T>, y: T) -> (T, T)
+where
+    T: Copy + PartialEq + Sub<Output = T> + Mul<Output = T> + Div<Output = T>,
+{
     // Compute the coordinates of the two points of the line segment.
     let x1 = segment.p1.x;
     let y1 = segment.p1.y;
@@ -26,3 +32,75 @@ pub fn intersection(segment: LineSegment, y: f64) -> (f64, f64) {
         (x, y)
     }
 }
+
+// Computes the intersection of two line segments, if one exists.
+//
+// Solves `p + t*r = q + u*s` for `t` and `u` via the cross-product method;
+// parallel direction vectors (`r.cross(s) == 0`, including collinear
+// overlap) give `None`, otherwise the segments only actually cross if both
+// parameters land in `[0, 1]`. Stays `f64`-specific since those parametric
+// bounds only make sense for floating-point coordinates.
+pub fn segment_intersection(a: LineSegment<f64>, b: LineSegment<f64>) -> Option<Point<f64>> {
+    let p = a.p1;
+    let r = a.p2 - a.p1;
+    let q = b.p1;
+    let s = b.p2 - b.p1;
+
+    let rs = r.cross(s);
+    if rs == 0.0 {
+        return None;
+    }
+
+    let qp = q - p;
+    let t = qp.cross(s) / rs;
+    let u = qp.cross(r) / rs;
+
+    if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+        Some(p + r * t)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seg(x1: f64, y1: f64, x2: f64, y2: f64) -> LineSegment<f64> {
+        LineSegment {
+            p1: Point { x: x1, y: y1 },
+            p2: Point { x: x2, y: y2 },
+        }
+    }
+
+    #[test]
+    fn crossing_segments_intersect_at_their_midpoint() {
+        let a = seg(0.0, 0.0, 2.0, 2.0);
+        let b = seg(0.0, 2.0, 2.0, 0.0);
+        let p = segment_intersection(a, b).unwrap();
+        assert!((p.x - 1.0).abs() < 1e-9);
+        assert!((p.y - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn parallel_segments_do_not_intersect() {
+        let a = seg(0.0, 0.0, 1.0, 0.0);
+        let b = seg(0.0, 1.0, 1.0, 1.0);
+        assert_eq!(segment_intersection(a, b), None);
+    }
+
+    #[test]
+    fn segments_that_would_cross_if_extended_do_not_intersect() {
+        let a = seg(0.0, 0.0, 1.0, 1.0);
+        let b = seg(5.0, 0.0, 4.0, 1.0);
+        assert_eq!(segment_intersection(a, b), None);
+    }
+
+    #[test]
+    fn scanline_intersection_of_diagonal_segment() {
+        let s = seg(0.0, 0.0, 2.0, 4.0);
+        let (x, y) = intersection(s, 2.0);
+        assert!((x - 1.0).abs() < 1e-9);
+        assert_eq!(y, 2.0);
+    }
+}
@@ -1,16 +1,21 @@
-use std::ops::Sub;
+use std::ops::{Add, Mul, Sub};
 
-// A struct to represent a point in 2D space.
+// A point in 2D space, generic over its coordinate scalar type (e.g. `f64`
+// or exact integer coordinates like `i64`).
 #[derive(Clone, Copy, Debug)]
-pub struct Point {
-    pub x: f64,
-    pub y: f64,
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
 }
 
-impl Sub for Point {
-    type Output = Point;
+// Alias kept for source compatibility with code written against the
+// original `f64`-only `Point`.
+pub type PointF = Point<f64>;
 
-    fn sub(self, other: Point) -> Point {
+impl<T: Sub<Output = T>> Sub for Point<T> {
+    type Output = Point<T>;
+
+    fn sub(self, other: Point<T>) -> Point<T> {
         Point {
             x: self.x - other.x,
             y: self.y - other.y,
@@ -18,13 +23,35 @@ impl Sub for Point {
     }
 }
 
-impl PartialEq for Point {
+impl<T: Add<Output = T>> Add for Point<T> {
+    type Output = Point<T>;
+
+    fn add(self, other: Point<T>) -> Point<T> {
+        Point {
+            x: self.x + other.x,
+            y: self.y + other.y,
+        }
+    }
+}
+
+impl<T: Mul<Output = T> + Copy> Mul<T> for Point<T> {
+    type Output = Point<T>;
+
+    fn mul(self, scalar: T) -> Point<T> {
+        Point {
+            x: self.x * scalar,
+            y: self.y * scalar,
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for Point<T> {
     fn eq(&self, other: &Self) -> bool {
         self.x == other.x && self.y == other.y
     }
 }
 
-impl Ord for Point {
+impl<T: PartialOrd + Copy> Ord for Point<T> {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         if self.x < other.x {
             std::cmp::Ordering::Less
@@ -40,16 +67,82 @@ impl Ord for Point {
     }
 }
 
-impl PartialOrd for Point {
+impl<T: PartialOrd + Copy> PartialOrd for Point<T> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
     }
 }
 
-impl Eq for Point {}
+impl<T: PartialEq> Eq for Point<T> {}
 
-impl Point {
-    pub fn cross(&self, other: Point) -> f64 {
+impl<T: Mul<Output = T> + Sub<Output = T> + Copy> Point<T> {
+    pub fn cross(&self, other: Point<T>) -> T {
         self.x * other.y - self.y * other.x
     }
 }
+
+impl Point<f64> {
+    // Tests whether `self` and `other` are within `eps` of each other on
+    // both axes, so near-duplicate points produced by independent
+    // floating-point computations (e.g. the same crossing computed from
+    // each side of an intersection) can be recognized as "the same" vertex.
+    pub fn approx_eq(&self, other: Point<f64>, eps: f64) -> bool {
+        (self.x - other.x).abs() <= eps && (self.y - other.y).abs() <= eps
+    }
+
+    // An ordering that treats coordinates within `eps` of each other as
+    // equal: compares `x` first with a banded threshold, and only falls
+    // through to `y` once the `x` bands match. Sorting with this lets
+    // near-duplicate points end up adjacent so they can be merged with a
+    // single dedup pass instead of needing an exact match.
+    pub fn banded_cmp(&self, other: &Point<f64>, eps: f64) -> std::cmp::Ordering {
+        if (self.x - other.x).abs() <= eps {
+            if (self.y - other.y).abs() <= eps {
+                std::cmp::Ordering::Equal
+            } else {
+                self.y.partial_cmp(&other.y).unwrap()
+            }
+        } else {
+            self.x.partial_cmp(&other.x).unwrap()
+        }
+    }
+}
+
+// Merges points within `eps` of each other in place, so coincident vertices
+// that differ only by floating-point noise collapse to a single point
+// instead of being treated as distinct.
+pub fn dedup_close(points: &mut Vec<Point<f64>>, eps: f64) {
+    points.sort_by(|a, b| a.banded_cmp(b, eps));
+    points.dedup_by(|a, b| a.approx_eq(*b, eps));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn approx_eq_within_and_beyond_tolerance() {
+        let a = Point { x: 1.0, y: 1.0 };
+        assert!(a.approx_eq(Point { x: 1.0 + 1e-10, y: 1.0 }, 1e-9));
+        assert!(!a.approx_eq(Point { x: 1.1, y: 1.0 }, 1e-9));
+    }
+
+    #[test]
+    fn banded_cmp_treats_close_coordinates_as_equal() {
+        let a = Point { x: 1.0, y: 1.0 };
+        let b = Point { x: 1.0 + 1e-10, y: 2.0 };
+        assert_eq!(a.banded_cmp(&b, 1e-9), std::cmp::Ordering::Less);
+        assert_eq!(a.banded_cmp(&a, 1e-9), std::cmp::Ordering::Equal);
+    }
+
+    #[test]
+    fn dedup_close_merges_near_duplicate_points() {
+        let mut points = vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 0.0 + 1e-10, y: 0.0 },
+            Point { x: 1.0, y: 1.0 },
+        ];
+        dedup_close(&mut points, 1e-9);
+        assert_eq!(points.len(), 2);
+    }
+}
@@ -1,24 +1,54 @@
-use std::collections::BTreeSet;
-use std::f64;
+use std::collections::BTreeMap;
+use std::ops::{Add, Mul, Sub};
 
-use crate::{intersection::intersection, point::Point};
+use crate::{
+    intersection::{intersection, segment_intersection},
+    point::{dedup_close, Point},
+};
+
+// Tolerance used throughout the boolean-op pipeline to treat nearly
+// identical points (e.g. the same crossing computed from either polygon's
+// edge) as the same vertex.
+const EPSILON: f64 = 1e-9;
 
 // A struct to represent a line segment in 2D space.
 #[derive(Clone, Copy, Debug)]
-pub struct LineSegment {
-    pub p1: Point,
-    pub p2: Point,
+pub struct LineSegment<T> {
+    pub p1: Point<T>,
+    pub p2: Point<T>,
+}
+
+// The four boolean set operations `Polygon::boolean` can perform.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoolOp {
+    Union,
+    Intersection,
+    Difference,
+    Xor,
 }
 
-// A struct to represent a polygon as a list of points.
+// Which way a polygon's vertices wind.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Orientation {
+    CounterClockwise,
+    Clockwise,
+}
+
+// A polygon as a list of vertices, generic over the coordinate scalar type.
+// The combinatorial methods below work for any such type; the inherently
+// floating-point ones (boolean ops, triangulation, containment, centroid)
+// are implemented specifically for `Polygon<f64>` further down.
 #[derive(Clone, Debug)]
-pub struct Polygon {
-    pub points: Vec<Point>,
+pub struct Polygon<T> {
+    pub points: Vec<Point<T>>,
 }
 
-impl Polygon {
+impl<T> Polygon<T>
+where
+    T: Copy + Default + PartialOrd + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
     // Computes the edges of the polygon as a list of line segments.
-    fn edges(&self) -> Vec<LineSegment> {
+    fn edges(&self) -> Vec<LineSegment<T>> {
         let mut edges = vec![];
         for i in 0..self.points.len() {
             let j = (i + 1) % self.points.len();
@@ -30,292 +60,866 @@ impl Polygon {
         edges
     }
 
-    // Computes the bounding box of the polygon.
-    pub fn bounding_box(&self) -> (f64, f64, f64, f64) {
-        let mut min_x = f64::INFINITY;
-        let mut min_y = f64::INFINITY;
-        let mut max_x = f64::NEG_INFINITY;
-        let mut max_y = f64::NEG_INFINITY;
-        for point in &self.points {
-            min_x = min_x.min(point.x);
-            min_y = min_y.min(point.y);
-            max_x = max_x.max(point.x);
-            max_y = max_y.max(point.y);
+    // Computes the bounding box of the polygon, or `None` if it has no
+    // vertices.
+    pub fn bounding_box(&self) -> Option<(T, T, T, T)> {
+        let first = self.points.first()?;
+        let mut min_x = first.x;
+        let mut min_y = first.y;
+        let mut max_x = first.x;
+        let mut max_y = first.y;
+        for point in &self.points[1..] {
+            if point.x < min_x {
+                min_x = point.x;
+            }
+            if point.x > max_x {
+                max_x = point.x;
+            }
+            if point.y < min_y {
+                min_y = point.y;
+            }
+            if point.y > max_y {
+                max_y = point.y;
+            }
         }
-        (min_x, min_y, max_x, max_y)
+        Some((min_x, min_y, max_x, max_y))
     }
 
-    // Computes the union of this polygon with another polygon using a
-    // scanline algorithm.
-    pub fn union(&self, other: &Polygon) -> Polygon {
-        // Create a set of points to store the result of the union.
-        let mut result = BTreeSet::new();
-
-        // Add the points of both polygons to the set of points.
-        for point in &self.points {
-            result.insert(*point);
+    // Reports which way the vertices wind, from the sign of twice the
+    // signed area (the shoelace sum), without needing a division.
+    pub fn orientation(&self) -> Orientation {
+        let mut area = T::default();
+        for i in 0..self.points.len() {
+            let j = (i + 1) % self.points.len();
+            area = area + self.points[i].cross(self.points[j]);
+        }
+        if area > T::default() {
+            Orientation::CounterClockwise
+        } else {
+            Orientation::Clockwise
         }
-        for point in &other.points {
-            result.insert(*point);
+    }
+
+    // Reverses the vertex list in place if it winds clockwise, so downstream
+    // algorithms can assume a canonical counter-clockwise winding.
+    pub fn ensure_ccw(&mut self) {
+        if self.orientation() == Orientation::Clockwise {
+            self.points.reverse();
         }
+    }
 
-        // Create a vector of all the points in the set, sorted by y-coordinate.
-        let mut points: Vec<Point> = result.iter().copied().collect();
+    pub fn convex_hull(&self) -> Polygon<T> {
+        // Create a vector of all the points in the polygon.
+        let mut points: Vec<Point<T>> = self.points.to_vec();
+
+        // Sort the points by y-coordinate.
         points.sort_by(|point1, point2| point1.y.partial_cmp(&point2.y).unwrap());
 
-        let mut y = f64::NEG_INFINITY;
-        // Iterate over the scanlines in the bounding box.
-        for point in points {
-            // Check if the y-coordinate of the point is different from the previous point.
-            if point.y != y {
-                // If it is, we have reached a new scanline.
-                y = point.y;
-
-                // Create a set of points to store the intersections with the scanline.
-                let mut intersections = BTreeSet::new();
-
-                // Compute the intersections of the scanline with the edges of the first polygon.
-                for edge in self.edges() {
-                    // Check if the scanline intersects the edge.
-                    let (x, _) = intersection(edge, y);
-                    if x.is_finite() {
-                        // Add the intersection point to the set of intersections.
-                        intersections.insert(Point { x, y });
-                    }
+        // Create a vector to store the result points.
+        let mut result: Vec<Point<T>> = Vec::new();
+
+        // Compute the lower hull.
+        for point in &points {
+            while result.len() >= 2
+                && (result[result.len() - 2] - result[result.len() - 1])
+                    .cross(*point - result[result.len() - 1])
+                    <= T::default()
+            {
+                result.pop();
+            }
+            result.push(*point);
+        }
+
+        // Compute the upper hull.
+        let n = result.len() + 1;
+        for point in points.iter().rev() {
+            while result.len() >= n
+                && (result[result.len() - 2] - result[result.len() - 1])
+                    .cross(*point - result[result.len() - 1])
+                    <= T::default()
+            {
+                result.pop();
+            }
+            result.push(*point);
+        }
+
+        // Return the result as a polygon.
+        Polygon { points: result }
+    }
+}
+
+impl Polygon<f64> {
+    // Computes the union of this polygon with another polygon.
+    pub fn union(&self, other: &Polygon<f64>) -> Vec<Polygon<f64>> {
+        self.boolean(other, BoolOp::Union)
+    }
+
+    // Computes the intersection of this polygon with another polygon.
+    pub fn intersection(&self, other: &Polygon<f64>) -> Vec<Polygon<f64>> {
+        self.boolean(other, BoolOp::Intersection)
+    }
+
+    // Computes the difference between two polygons (self minus other).
+    pub fn difference(&self, other: &Polygon<f64>) -> Vec<Polygon<f64>> {
+        self.boolean(other, BoolOp::Difference)
+    }
+
+    // Computes the symmetric difference of two polygons.
+    pub fn xor(&self, other: &Polygon<f64>) -> Vec<Polygon<f64>> {
+        self.boolean(other, BoolOp::Xor)
+    }
+
+    // Computes a boolean set operation between this polygon and another.
+    //
+    // Edges of both polygons are cut at every crossing, then each fragment
+    // is classified against the *other* source polygon (never its own,
+    // since a fragment's midpoint sits exactly on its own polygon's
+    // boundary, where `contains`'s ray-casting parity is inconsistent). A
+    // fragment that coincides with an edge of the other polygon (shared or
+    // touching edges, e.g. identical or flush-adjacent shapes) can't be
+    // tested this way either, since its midpoint then sits on *both*
+    // boundaries; those are classified by `keep_shared_edge` instead, from
+    // whether the two source edges run the same way along the shared span.
+    // Brute-force O(n*m) classify-and-stitch, not a sweepline.
+    pub fn boolean(&self, other: &Polygon<f64>, op: BoolOp) -> Vec<Polygon<f64>> {
+        let mut self_poly = self.clone();
+        let mut other_poly = other.clone();
+        self_poly.ensure_ccw();
+        other_poly.ensure_ccw();
+
+        let self_edges = self_poly.edges();
+        let other_edges = other_poly.edges();
+
+        let self_fragments = split_edges(&self_edges, &other_edges);
+        let other_fragments = split_edges(&other_edges, &self_edges);
+
+        let mut other_claimed = vec![false; other_fragments.len()];
+        let mut kept = Vec::new();
+
+        for frag in &self_fragments {
+            let shared = other_fragments.iter().enumerate().find_map(|(i, other_frag)| {
+                if other_claimed[i] {
+                    return None;
                 }
+                same_segment(frag, other_frag, EPSILON).map(|same_direction| (i, same_direction))
+            });
 
-                // Compute the intersections of the scanline with the edges of the second polygon.
-                for edge in other.edges() {
-                    // Check if the scanline intersects the edge.
-                    let (x, _) = intersection(edge, y);
-                    if x.is_finite() {
-                        // Add the intersection point to the set of intersections.
-                        intersections.insert(Point { x, y });
+            match shared {
+                Some((i, same_direction)) => {
+                    other_claimed[i] = true;
+                    if keep_shared_edge(op, same_direction) {
+                        kept.push(*frag);
                     }
                 }
-
-                // Add the intersections to the result.
-                for intersection in intersections {
-                    result.insert(intersection);
+                None => {
+                    let inside_other = other_poly.contains(midpoint(frag));
+                    let keep = match op {
+                        BoolOp::Union => !inside_other,
+                        BoolOp::Intersection => inside_other,
+                        BoolOp::Difference => !inside_other,
+                        BoolOp::Xor => true,
+                    };
+                    if keep {
+                        kept.push(*frag);
+                    }
                 }
             }
         }
 
-        // Return the result as a polygon.
-        Polygon {
-            points: result.into_iter().collect(),
+        for (i, frag) in other_fragments.iter().enumerate() {
+            if other_claimed[i] {
+                continue;
+            }
+            let inside_self = self_poly.contains(midpoint(frag));
+            let keep = match op {
+                BoolOp::Union => !inside_self,
+                BoolOp::Intersection => inside_self,
+                BoolOp::Difference => inside_self,
+                BoolOp::Xor => true,
+            };
+            if keep {
+                kept.push(*frag);
+            }
         }
+
+        // The same crossing is computed twice — once from each polygon's
+        // edge — and the two floating-point results rarely land on exactly
+        // the same bits. Snap fragment endpoints within `EPSILON` of each
+        // other together first, so `stitch_rings`'s exact-match adjacency
+        // lookup actually finds them.
+        stitch_rings(snap_fragments(kept, EPSILON))
     }
 
-    // Computes the difference between two polygons.
-    fn difference(&self, other: &Polygon) -> Polygon {
-        // Create a set of points to store the result of the difference.
-        let mut result = BTreeSet::new();
+    // Computes the signed area of the polygon via the shoelace formula.
+    // Positive when the vertices wind counter-clockwise, negative when they
+    // wind clockwise.
+    pub fn signed_area(&self) -> f64 {
+        let mut area = 0.0;
+        for i in 0..self.points.len() {
+            let j = (i + 1) % self.points.len();
+            area += self.points[i].x * self.points[j].y - self.points[j].x * self.points[i].y;
+        }
+        area / 2.0
+    }
 
-        // Add the points of the first polygon to the set of points.
-        for point in &self.points {
-            result.insert(point);
+    // Computes the area-weighted centroid of the polygon (unlike a plain
+    // vertex average, this isn't skewed by how densely the boundary is
+    // sampled).
+    pub fn centroid(&self) -> Point<f64> {
+        let area = self.signed_area();
+        if area == 0.0 {
+            // Degenerate polygon (zero area): fall back to the vertex mean.
+            let mut x_sum = 0.0;
+            let mut y_sum = 0.0;
+            for point in &self.points {
+                x_sum += point.x;
+                y_sum += point.y;
+            }
+            let n = self.points.len() as f64;
+            return Point {
+                x: x_sum / n,
+                y: y_sum / n,
+            };
         }
 
-        // Create a vector of all the points in the set, sorted by y-coordinate.
-        let mut points: Vec<&Point> = result.iter().copied().collect();
-        points.sort_by(|point1, point2| point1.y.partial_cmp(&point2.y).unwrap());
+        let mut cx = 0.0;
+        let mut cy = 0.0;
+        for i in 0..self.points.len() {
+            let j = (i + 1) % self.points.len();
+            let cross = self.points[i].x * self.points[j].y - self.points[j].x * self.points[i].y;
+            cx += (self.points[i].x + self.points[j].x) * cross;
+            cy += (self.points[i].y + self.points[j].y) * cross;
+        }
+        let factor = 1.0 / (6.0 * area);
+        Point {
+            x: cx * factor,
+            y: cy * factor,
+        }
+    }
 
-        // Iterate over the points in the vector.
-        let mut y = f64::NEG_INFINITY;
-        let mut inside = false;
-        for point in points {
-            // Check if the y-coordinate of the point is different from the previous point.
-            if point.y != y {
-                // If it is, we have reached a new scanline.
-                y = point.y;
-
-                // Compute the intersections of the scanline with the edges of the second polygon.
-                let mut intersections = BTreeSet::new();
-                for edge in other.edges() {
-                    // Check if the scanline intersects the edge.
-                    let (x, _) = intersection(edge, y);
-                    if x.is_finite() {
-                        // Add the intersection point to the set of intersections.
-                        intersections.insert(Point { x, y });
-                    }
+    // Tests whether `p` lies inside the polygon, via ray-casting parity:
+    // cast a ray to the right from `p` and count the edges it crosses.
+    // An edge is only counted when it straddles `p.y` (one endpoint
+    // strictly above, the other at or below) and crosses to the right of `p`;
+    // this handles rays that pass exactly through a vertex deterministically,
+    // since the edge above the vertex counts it and the edge below does not.
+    pub fn contains(&self, p: Point<f64>) -> bool {
+        let mut crossings = 0;
+        for edge in self.edges() {
+            let straddles = (p.y < edge.p1.y) != (p.y < edge.p2.y);
+            if straddles {
+                let (x, _) = intersection(edge, p.y);
+                if x > p.x {
+                    crossings += 1;
                 }
+            }
+        }
+        crossings % 2 == 1
+    }
 
-                // Update the inside/outside state based on the intersections.
-                if intersections.len() % 2 == 0 {
-                    inside = !inside;
+    // Tests whether `other` lies entirely within this polygon: every vertex
+    // of `other` must be inside `self` or on its boundary, and no edge of
+    // `other` may properly cross an edge of `self` (a vertex could be
+    // inside while an edge still pokes out and back in between vertices).
+    pub fn contains_polygon(&self, other: &Polygon<f64>) -> bool {
+        if !other
+            .points
+            .iter()
+            .all(|&p| self.contains(p) || self.on_boundary(p, EPSILON))
+        {
+            return false;
+        }
+        let self_edges = self.edges();
+        let other_edges = other.edges();
+        for a in &self_edges {
+            for b in &other_edges {
+                if proper_crossing(a, b, EPSILON) {
+                    return false;
                 }
             }
+        }
+        true
+    }
 
-            // Check if the point is inside the second polygon.
-            if inside {
-                // If it is, remove it from the result.
-                result.remove(point);
-            }
+    // Tests whether `p` lies within `eps` of some edge of the polygon.
+    fn on_boundary(&self, p: Point<f64>, eps: f64) -> bool {
+        self.edges().iter().any(|edge| point_near_segment(p, edge, eps))
+    }
+
+    // Decomposes a simple polygon into triangles via ear clipping: repeatedly
+    // clips off a convex vertex whose triangle contains no other vertex,
+    // until three vertices remain. Gives up after `n` consecutive failed
+    // scans so degenerate input can't loop forever.
+    pub fn triangulate(&self) -> Vec<[Point<f64>; 3]> {
+        let mut poly = self.clone();
+        poly.ensure_ccw();
+        let n = poly.points.len();
+        if n < 3 {
+            return Vec::new();
         }
 
-        // Convert the result set to a vector of points.
-        let mut points: Vec<Point> = result.into_iter().copied().collect();
+        // Doubly linked ring of vertex indices, so clipped vertices can be
+        // skipped in O(1) without shifting the rest of the polygon.
+        let mut next: Vec<usize> = (0..n).map(|i| (i + 1) % n).collect();
+        let mut prev: Vec<usize> = (0..n).map(|i| (i + n - 1) % n).collect();
+        let mut active = vec![true; n];
+
+        let mut triangles = Vec::new();
+        let mut remaining = n;
+        let mut current = 0;
+        let mut failed_scans = 0;
+
+        while remaining > 3 && failed_scans < n {
+            let a = prev[current];
+            let b = current;
+            let c = next[current];
+
+            if is_ear(&poly.points, &next, a, b, c, &active) {
+                triangles.push([poly.points[a], poly.points[b], poly.points[c]]);
+                active[b] = false;
+                next[a] = c;
+                prev[c] = a;
+                remaining -= 1;
+                failed_scans = 0;
+                current = a;
+            } else {
+                failed_scans += 1;
+                current = next[current];
+            }
+        }
 
-        // Sort the points in counter-clockwise order.
-        let centroid = self.centroid();
-        points.sort_by(|point1, point2| {
-            (*point2 - centroid)
-                .cross(*point1 - centroid)
-                .partial_cmp(&0.0)
-                .unwrap()
-        });
+        if remaining == 3 {
+            let a = prev[current];
+            let b = current;
+            let c = next[current];
+            triangles.push([poly.points[a], poly.points[b], poly.points[c]]);
+        }
 
-        // Return the result as a polygon.
-        Polygon { points }
+        triangles
     }
+}
 
-    // Computes the difference between two polygons.
-    fn fixed_difference(&self, other: &Polygon) -> Vec<Polygon> {
-        // Create a set of points to store the result of the difference.
-        let mut result = BTreeSet::new();
+// The midpoint of a fragment, used as its representative sample point for
+// containment tests.
+fn midpoint(frag: &LineSegment<f64>) -> Point<f64> {
+    Point {
+        x: (frag.p1.x + frag.p2.x) / 2.0,
+        y: (frag.p1.y + frag.p2.y) / 2.0,
+    }
+}
 
-        // Add the points of both polygons to the set of points.
-        for point in &self.points {
-            result.insert(point);
-        }
-        for point in &other.points {
-            result.insert(point);
-        }
+// Tests whether `p` is within `eps` of the closest point on `seg`.
+fn point_near_segment(p: Point<f64>, seg: &LineSegment<f64>, eps: f64) -> bool {
+    let d = seg.p2 - seg.p1;
+    let len2 = d.x * d.x + d.y * d.y;
+    if len2 < eps * eps {
+        return p.approx_eq(seg.p1, eps);
+    }
+    let t = ((p.x - seg.p1.x) * d.x + (p.y - seg.p1.y) * d.y) / len2;
+    let t = t.clamp(0.0, 1.0);
+    let closest = seg.p1 + d * t;
+    closest.approx_eq(p, eps)
+}
 
-        // Create a vector of all the points in the set, sorted by y-coordinate.
-        let mut points: Vec<&Point> = result.iter().copied().collect();
-        points.sort_by(|point1, point2| point1.y.partial_cmp(&point2.y).unwrap());
+// Tests whether `a` and `b` cross at a point strictly interior to both
+// segments. An endpoint touch (one segment's vertex landing on the other)
+// doesn't count: that just means a vertex sits on the other polygon's
+// boundary, not that an edge pokes through it.
+fn proper_crossing(a: &LineSegment<f64>, b: &LineSegment<f64>, eps: f64) -> bool {
+    let p = a.p1;
+    let r = a.p2 - a.p1;
+    let q = b.p1;
+    let s = b.p2 - b.p1;
+
+    let rs = r.cross(s);
+    if rs.abs() < eps {
+        return false;
+    }
 
-        // Create a vector to store the result polygons.
-        let mut polygons = Vec::new();
-
-        // Iterate over the points in the vector.
-        let mut y = f64::NEG_INFINITY;
-        let mut inside = false;
-        for point in points {
-            // Check if the y-coordinate of the point is different from the previous point.
-            if point.y != y {
-                // If it is, we have reached a new scanline.
-                y = point.y;
-
-                // Compute the intersections of the scanline with the edges of the second polygon.
-                let mut intersections = BTreeSet::new();
-                for edge in other.edges() {
-                    // Check if the scanline intersects the edge.
-                    let (x, _) = intersection(edge, y);
-                    if x.is_finite() {
-                        // Add the intersection point to the set of intersections.
-                        intersections.insert(Point { x, y });
-                    }
-                }
+    let qp = q - p;
+    let t = qp.cross(s) / rs;
+    let u = qp.cross(r) / rs;
 
-                // Update the inside/outside state based on the intersections.
-                if intersections.len() % 2 == 0 {
-                    inside = !inside;
-                }
+    t > eps && t < 1.0 - eps && u > eps && u < 1.0 - eps
+}
 
-                // Check if the point is inside the second polygon.
-                if inside {
-                    // If it is, remove it from the result.
-                    result.remove(point);
-                } else if !intersections.is_empty() {
-                    // If it is not, and there are intersections at this scanline,
-                    // create a new polygon from the points in the result.
-                    let mut points: Vec<Point> = result.iter().map(|point| **point).collect();
-
-                    // Sort the points in counter-clockwise order.
-                    let centroid = self.centroid();
-                    points.sort_by(|point1, point2| {
-                        (*point2 - centroid)
-                            .cross(*point1 - centroid)
-                            .partial_cmp(&0.0)
-                            .unwrap()
-                    });
-
-                    // Add the new polygon to the result vector.
-                    polygons.push(Polygon { points });
-
-                    // Clear the result set for the next polygon.
-                    result.clear();
+// Cuts every edge in `edges` at each point where it crosses an edge in
+// `other_edges`, returning the resulting straight fragments in no
+// particular order. Splitting first means classification never has to reason
+// about an edge that is partly inside and partly outside the other
+// polygon.
+fn split_edges(
+    edges: &[LineSegment<f64>],
+    other_edges: &[LineSegment<f64>],
+) -> Vec<LineSegment<f64>> {
+    let mut fragments = Vec::new();
+    for &edge in edges {
+        let dx = edge.p2.x - edge.p1.x;
+        let dy = edge.p2.y - edge.p1.y;
+        let param = |p: Point<f64>| -> f64 {
+            if dx.abs() > dy.abs() {
+                (p.x - edge.p1.x) / dx
+            } else {
+                (p.y - edge.p1.y) / dy
+            }
+        };
+
+        // Parametric positions (t in [0, 1]) along `edge` where it crosses
+        // some edge of the other polygon, plus the two endpoints.
+        let mut ts = vec![0.0, 1.0];
+        for &other in other_edges {
+            if let Some(p) = segment_intersection(edge, other) {
+                ts.push(param(p).clamp(0.0, 1.0));
+            } else if is_collinear(&edge, &other, EPSILON) {
+                // A crossing point can't capture a shared or overlapping
+                // collinear edge (`segment_intersection` returns `None` for
+                // parallel edges), so cut at the other edge's endpoints
+                // instead, wherever they land along this one.
+                for p in [other.p1, other.p2] {
+                    let t = param(p);
+                    if (0.0..=1.0).contains(&t) {
+                        ts.push(t);
+                    }
                 }
             }
+        }
+        ts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        ts.dedup_by(|a, b| (*a - *b).abs() < EPSILON);
 
-            // If there are remaining points in the result set,
-            // create one more polygon from the points.
-            if !result.is_empty() {
-                let mut points: Vec<Point> = result.iter().map(|point| **point).collect();
-
-                // Sort the points in counter-clockwise order.
-                let centroid = self.centroid();
-                points.sort_by(|point1, point2| {
-                    (*point2 - centroid)
-                        .cross(*point1 - centroid)
-                        .partial_cmp(&0.0)
-                        .unwrap()
-                });
-
-                // Add the new polygon to the result vector.
-                polygons.push(Polygon { points });
+        for pair in ts.windows(2) {
+            let (t0, t1) = (pair[0], pair[1]);
+            if t1 - t0 < EPSILON {
+                continue;
             }
+            fragments.push(LineSegment {
+                p1: edge.p1 + (edge.p2 - edge.p1) * t0,
+                p2: edge.p1 + (edge.p2 - edge.p1) * t1,
+            });
         }
-        // Return the result vector of polygons.
-        polygons
     }
+    fragments
+}
+
+// Tests whether `other` lies on the same infinite line as `edge`.
+fn is_collinear(edge: &LineSegment<f64>, other: &LineSegment<f64>, eps: f64) -> bool {
+    let d = edge.p2 - edge.p1;
+    d.cross(other.p1 - edge.p1).abs() < eps && d.cross(other.p2 - edge.p1).abs() < eps
+}
+
+// Returns `Some(same_direction)` when `a` and `b` are (within `eps`) the
+// same segment, where `same_direction` says whether they run the same way
+// or opposite ways along it.
+fn same_segment(a: &LineSegment<f64>, b: &LineSegment<f64>, eps: f64) -> Option<bool> {
+    if a.p1.approx_eq(b.p1, eps) && a.p2.approx_eq(b.p2, eps) {
+        Some(true)
+    } else if a.p1.approx_eq(b.p2, eps) && a.p2.approx_eq(b.p1, eps) {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+// Decides whether to keep an edge shared between both polygons (e.g. two
+// identical shapes, or flush-adjacent ones), which can't be classified by
+// `contains` since it sits on both boundaries at once. Both polygons are
+// canonicalized to CCW winding first, so "same direction" means their
+// interiors are on the same side of the shared edge, and "opposite
+// direction" means the edge sits between the two interiors instead.
+fn keep_shared_edge(op: BoolOp, same_direction: bool) -> bool {
+    match op {
+        BoolOp::Union | BoolOp::Intersection => same_direction,
+        BoolOp::Difference => !same_direction,
+        BoolOp::Xor => false,
+    }
+}
+
+// Replaces every fragment endpoint with a canonical point shared by all
+// other endpoints within `eps` of it, via `point::dedup_close`. Without
+// this, two fragments that meet at "the same" crossing point (computed via
+// two different edges' parametrizations) can differ by a few ULPs and end
+// up as distinct keys in `stitch_rings`'s adjacency map, breaking the ring
+// open instead of closing it.
+fn snap_fragments(fragments: Vec<LineSegment<f64>>, eps: f64) -> Vec<LineSegment<f64>> {
+    let mut canonical: Vec<Point<f64>> = fragments.iter().flat_map(|f| [f.p1, f.p2]).collect();
+    dedup_close(&mut canonical, eps);
+
+    let snap = |p: Point<f64>| -> Point<f64> {
+        canonical
+            .iter()
+            .copied()
+            .find(|c| c.approx_eq(p, eps))
+            .unwrap_or(p)
+    };
+
+    fragments
+        .into_iter()
+        .map(|f| LineSegment {
+            p1: snap(f.p1),
+            p2: snap(f.p2),
+        })
+        .collect()
+}
 
-    pub fn centroid(&self) -> Point {
-        // Compute the sum of the x- and y-coordinates of the points.
-        let mut x_sum = 0.0;
-        let mut y_sum = 0.0;
-        for point in &self.points {
-            x_sum += point.x;
-            y_sum += point.y;
+// Walks a soup of edge fragments end-to-end into closed rings, dropping
+// dangling chains that never close. Rings nested inside an odd number of
+// other rings are holes and wind clockwise; the rest are shells and wind
+// counter-clockwise, so callers can tell them apart by winding direction.
+// Rings are then ordered largest-area-first, so a shell always precedes
+// the holes nested inside it.
+fn stitch_rings(fragments: Vec<LineSegment<f64>>) -> Vec<Polygon<f64>> {
+    let mut adjacency: BTreeMap<Point<f64>, Vec<usize>> = BTreeMap::new();
+    for (i, frag) in fragments.iter().enumerate() {
+        adjacency.entry(frag.p1).or_default().push(i);
+        adjacency.entry(frag.p2).or_default().push(i);
+    }
+
+    let mut used = vec![false; fragments.len()];
+    let mut rings = Vec::new();
+
+    for start in 0..fragments.len() {
+        if used[start] {
+            continue;
+        }
+        used[start] = true;
+        let origin = fragments[start].p1;
+        let mut points = vec![origin];
+        let mut current = fragments[start].p2;
+        points.push(current);
+
+        while current != origin {
+            let next = adjacency
+                .get(&current)
+                .and_then(|candidates| candidates.iter().copied().find(|&i| !used[i]));
+            match next {
+                Some(i) => {
+                    used[i] = true;
+                    let frag = &fragments[i];
+                    current = if frag.p1 == current { frag.p2 } else { frag.p1 };
+                    points.push(current);
+                }
+                None => break,
+            }
         }
 
-        // Compute the centroid as the average of the coordinates.
-        let n = self.points.len() as f64;
-        Point {
-            x: x_sum / n,
-            y: y_sum / n,
+        if points.len() > 3 && points.first() == points.last() {
+            points.pop();
+            rings.push(Polygon { points });
         }
     }
 
-    pub fn convex_hull(&self) -> Polygon {
-        // Create a vector of all the points in the polygon.
-        let mut points: Vec<Point> = self.points.to_vec();
+    let centroids: Vec<Point<f64>> = rings.iter().map(|ring| ring.centroid()).collect();
+    for i in 0..rings.len() {
+        let nested_count = (0..rings.len())
+            .filter(|&j| j != i && rings[j].contains(centroids[i]))
+            .count();
+        let wants_cw = nested_count % 2 == 1;
+        if (rings[i].orientation() == Orientation::Clockwise) != wants_cw {
+            rings[i].points.reverse();
+        }
+    }
 
-        // Sort the points by x-coordinate.
-        points.sort_by(|point1, point2| point1.y.partial_cmp(&point2.y).unwrap());
+    rings.sort_by(|a, b| {
+        b.signed_area()
+            .abs()
+            .partial_cmp(&a.signed_area().abs())
+            .unwrap()
+    });
+    rings
+}
 
-        // Create a vector to store the result points.
-        let mut result: Vec<Point> = Vec::new();
+// Tests whether vertex `v` is convex, assuming a counter-clockwise polygon:
+// a left turn from the incoming edge `a -> v` to the outgoing edge `v -> c`.
+fn is_convex(a: Point<f64>, v: Point<f64>, c: Point<f64>) -> bool {
+    (v - a).cross(c - v) > 0.0
+}
 
-        // Compute the lower hull.
-        for point in &points {
-            while result.len() >= 2
-                && (result[result.len() - 2] - result[result.len() - 1])
-                    .cross(*point - result[result.len() - 1])
-                    <= 0.0
-            {
-                result.pop();
-            }
-            result.push(*point);
+// Tests whether `p` lies inside (or on) the triangle `a, b, c`, via three
+// cross products that must all agree in sign.
+fn point_in_triangle(p: Point<f64>, a: Point<f64>, b: Point<f64>, c: Point<f64>) -> bool {
+    let d1 = (b - a).cross(p - a);
+    let d2 = (c - b).cross(p - b);
+    let d3 = (a - c).cross(p - c);
+
+    let has_neg = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+    let has_pos = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+    !(has_neg && has_pos)
+}
+
+// Tests whether clipping the vertex `b` (with ring neighbors `a` and `c`)
+// off would produce a valid ear: `b` must be convex, and no other active
+// vertex of the ring may lie inside the candidate triangle.
+fn is_ear(
+    points: &[Point<f64>],
+    next: &[usize],
+    a: usize,
+    b: usize,
+    c: usize,
+    active: &[bool],
+) -> bool {
+    if !is_convex(points[a], points[b], points[c]) {
+        return false;
+    }
+
+    let mut i = next[c];
+    while i != a {
+        if active[i] && point_in_triangle(points[i], points[a], points[b], points[c]) {
+            return false;
         }
+        i = next[i];
+    }
+    true
+}
 
-        // Compute the upper hull.
-        let n = result.len() + 1;
-        for point in points.iter().rev() {
-            while result.len() >= n
-                && (result[result.len() - 2] - result[result.len() - 1])
-                    .cross(*point - result[result.len() - 1])
-                    <= 0.0
-            {
-                result.pop();
-            }
-            result.push(*point);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x0: f64, y0: f64, x1: f64, y1: f64) -> Polygon<f64> {
+        Polygon {
+            points: vec![
+                Point { x: x0, y: y0 },
+                Point { x: x1, y: y0 },
+                Point { x: x1, y: y1 },
+                Point { x: x0, y: y1 },
+            ],
         }
+    }
 
-        // Return the result as a polygon.
-        Polygon { points: result }
+    #[test]
+    fn signed_area_is_positive_for_ccw_and_negative_for_cw() {
+        let ccw = square(0.0, 0.0, 2.0, 2.0);
+        let mut cw = ccw.clone();
+        cw.points.reverse();
+        assert!((ccw.signed_area() - 4.0).abs() < 1e-9);
+        assert!((cw.signed_area() + 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn orientation_and_ensure_ccw() {
+        let mut cw = square(0.0, 0.0, 2.0, 2.0);
+        cw.points.reverse();
+        assert_eq!(cw.orientation(), Orientation::Clockwise);
+        cw.ensure_ccw();
+        assert_eq!(cw.orientation(), Orientation::CounterClockwise);
+    }
+
+    #[test]
+    fn centroid_of_square_is_its_center() {
+        let poly = square(0.0, 0.0, 2.0, 2.0);
+        let c = poly.centroid();
+        assert!((c.x - 1.0).abs() < 1e-9);
+        assert!((c.y - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn centroid_is_area_weighted_not_a_vertex_average() {
+        // An L-shaped hexagon where the plain vertex average (1.33, 1.33)
+        // differs from the area-weighted centroid (1.1, 1.1).
+        let poly = Polygon {
+            points: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 3.0, y: 0.0 },
+                Point { x: 3.0, y: 1.0 },
+                Point { x: 1.0, y: 1.0 },
+                Point { x: 1.0, y: 3.0 },
+                Point { x: 0.0, y: 3.0 },
+            ],
+        };
+        let c = poly.centroid();
+        assert!((c.x - 1.1).abs() < 1e-9);
+        assert!((c.y - 1.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn bounding_box_of_empty_polygon_is_none() {
+        let empty: Polygon<f64> = Polygon { points: vec![] };
+        assert_eq!(empty.bounding_box(), None);
+    }
+
+    #[test]
+    fn bounding_box_of_square() {
+        let poly = square(0.0, 0.0, 2.0, 3.0);
+        assert_eq!(poly.bounding_box(), Some((0.0, 0.0, 2.0, 3.0)));
+    }
+
+    #[test]
+    fn contains_point_inside_and_outside_square() {
+        let poly = square(0.0, 0.0, 2.0, 2.0);
+        assert!(poly.contains(Point { x: 1.0, y: 1.0 }));
+        assert!(!poly.contains(Point { x: 3.0, y: 1.0 }));
+    }
+
+    #[test]
+    fn contains_polygon_true_for_nested_square() {
+        let outer = square(0.0, 0.0, 4.0, 4.0);
+        let inner = square(1.0, 1.0, 2.0, 2.0);
+        assert!(outer.contains_polygon(&inner));
+        assert!(!inner.contains_polygon(&outer));
+    }
+
+    #[test]
+    fn contains_polygon_false_when_edges_cross() {
+        let a = square(0.0, 0.0, 2.0, 2.0);
+        let b = square(1.0, 1.0, 3.0, 3.0);
+        assert!(!a.contains_polygon(&b));
+    }
+
+    #[test]
+    fn contains_polygon_true_when_other_is_flush_against_the_boundary() {
+        let a = square(0.0, 0.0, 2.0, 2.0);
+        let flush = square(0.0, 0.0, 2.0, 1.0);
+        assert!(a.contains_polygon(&flush));
+    }
+
+    #[test]
+    fn triangulate_square_yields_two_triangles_covering_its_area() {
+        let poly = square(0.0, 0.0, 2.0, 2.0);
+        let triangles = poly.triangulate();
+        assert_eq!(triangles.len(), 2);
+        let total_area: f64 = triangles
+            .iter()
+            .map(|t| {
+                let tri = Polygon {
+                    points: t.to_vec(),
+                };
+                tri.signed_area().abs()
+            })
+            .sum();
+        assert!((total_area - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn triangulate_l_shape_covers_its_area() {
+        // A non-convex L-shaped hexagon with area 5 (see the centroid test above).
+        let poly = Polygon {
+            points: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 3.0, y: 0.0 },
+                Point { x: 3.0, y: 1.0 },
+                Point { x: 1.0, y: 1.0 },
+                Point { x: 1.0, y: 3.0 },
+                Point { x: 0.0, y: 3.0 },
+            ],
+        };
+        let triangles = poly.triangulate();
+        assert_eq!(triangles.len(), 4);
+        let total_area: f64 = triangles
+            .iter()
+            .map(|t| {
+                let tri = Polygon {
+                    points: t.to_vec(),
+                };
+                tri.signed_area().abs()
+            })
+            .sum();
+        assert!((total_area - 5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn generic_methods_work_with_integer_coordinates() {
+        let mut poly: Polygon<i64> = Polygon {
+            points: vec![
+                Point { x: 0, y: 0 },
+                Point { x: 0, y: 2 },
+                Point { x: 2, y: 2 },
+                Point { x: 2, y: 0 },
+            ],
+        };
+        assert_eq!(poly.orientation(), Orientation::Clockwise);
+        poly.ensure_ccw();
+        assert_eq!(poly.orientation(), Orientation::CounterClockwise);
+        assert_eq!(poly.bounding_box(), Some((0, 0, 2, 2)));
+    }
+
+    #[test]
+    fn convex_hull_drops_interior_point() {
+        let poly: Polygon<i64> = Polygon {
+            points: vec![
+                Point { x: 0, y: 0 },
+                Point { x: 4, y: 0 },
+                Point { x: 4, y: 4 },
+                Point { x: 0, y: 4 },
+                Point { x: 2, y: 2 },
+            ],
+        };
+        let hull = poly.convex_hull();
+        assert!(!hull.points.contains(&Point { x: 2, y: 2 }));
+        assert_eq!(
+            hull.points.iter().collect::<std::collections::BTreeSet<_>>().len(),
+            4
+        );
+    }
+
+    #[test]
+    fn union_of_overlapping_squares_is_one_ring() {
+        let a = square(0.0, 0.0, 2.0, 2.0);
+        let b = square(1.0, 1.0, 3.0, 3.0);
+        let result = a.union(&b);
+        assert_eq!(result.len(), 1);
+        assert!((result[0].signed_area().abs() - 7.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn union_of_disjoint_squares_is_two_rings() {
+        let a = square(0.0, 0.0, 1.0, 1.0);
+        let b = square(5.0, 5.0, 6.0, 6.0);
+        let result = a.union(&b);
+        assert_eq!(result.len(), 2);
+        let total_area: f64 = result.iter().map(|p| p.signed_area().abs()).sum();
+        assert!((total_area - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn intersection_of_overlapping_squares_is_the_overlap() {
+        let a = square(0.0, 0.0, 2.0, 2.0);
+        let b = square(1.0, 1.0, 3.0, 3.0);
+        let result = a.intersection(&b);
+        assert_eq!(result.len(), 1);
+        assert!((result[0].signed_area().abs() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn difference_with_nested_hole_returns_outer_and_hole_rings() {
+        let outer = square(0.0, 0.0, 4.0, 4.0);
+        let inner = square(1.0, 1.0, 2.0, 2.0);
+        let result = outer.difference(&inner);
+        assert_eq!(result.len(), 2);
+        assert!((result[0].signed_area().abs() - 16.0).abs() < 1e-6);
+        assert!((result[1].signed_area().abs() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn hole_ring_winds_opposite_of_its_shell() {
+        let outer = square(0.0, 0.0, 4.0, 4.0);
+        let inner = square(1.0, 1.0, 2.0, 2.0);
+        let result = outer.difference(&inner);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].orientation(), Orientation::CounterClockwise);
+        assert_eq!(result[1].orientation(), Orientation::Clockwise);
+    }
+
+    #[test]
+    fn union_of_identical_squares_is_the_square_itself() {
+        let a = square(0.0, 0.0, 2.0, 2.0);
+        let result = a.union(&a);
+        assert_eq!(result.len(), 1);
+        assert!((result[0].signed_area().abs() - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn difference_of_identical_squares_is_empty() {
+        let a = square(0.0, 0.0, 2.0, 2.0);
+        assert_eq!(a.difference(&a).len(), 0);
+    }
+
+    #[test]
+    fn union_of_flush_nested_square_is_the_outer_square() {
+        // `b` is flush against `a`'s left, right, and bottom edges, so three
+        // of its edges are collinear with (and a subset of) `a`'s own.
+        let a = square(0.0, 0.0, 2.0, 2.0);
+        let b = square(0.0, 0.0, 2.0, 1.0);
+        let result = a.union(&b);
+        assert_eq!(result.len(), 1);
+        assert!((result[0].signed_area().abs() - 4.0).abs() < 1e-9);
     }
 }